@@ -1,9 +1,12 @@
-use egui::emath::NumExt as _;
-use egui::epaint::{Color32, CornerRadius, RectShape, Shape, Stroke};
+use egui::epaint::{Color32, Shape, Stroke};
 
 use crate::{KlinePlot, Cursor, PlotPoint, PlotTransform};
 
-use super::{Orientation, PlotConfig, RectElement, add_rulers_and_text, highlighted_color};
+use super::{Orientation, PlotConfig, RectElement, add_rulers_and_text};
+use super::kline_orient::{
+    KlineOrient, Horizontal, Vertical, add_shapes_impl, bounds_max_impl, bounds_min_impl,
+    default_values_format_impl, values_with_ruler_impl,
+};
 
 /// Contains the values of a single box in a box plot.
 #[derive(Clone, Debug, PartialEq)]
@@ -44,12 +47,117 @@ impl KlineData{
             h,
         }
     }
+
+    /// Derive a box from raw samples, mirroring plotters' `Quartiles`.
+    ///
+    /// Equivalent to [`Self::from_samples_with_fence`] with the standard
+    /// Tukey fence multiplier of `1.5`.
+    pub fn from_samples(samples: &[f64]) -> Option<Self> {
+        Self::from_samples_with_fence(samples, 1.5)
+    }
+
+    /// Derive a box from raw samples, like [`Self::from_samples`], but with a
+    /// configurable Tukey fence multiplier.
+    ///
+    /// The samples are copied and sorted with a total float order; the
+    /// 25th/50th/75th percentiles (by linear interpolation between the
+    /// bracketing samples) become the box's `o`/`v`/`c` (Q1, median, Q3). The
+    /// whiskers (`l`/`h`, the box's min/max) are then pulled in to the
+    /// nearest actual sample inside the Tukey fences `Q1 - fence * IQR` and
+    /// `Q3 + fence * IQR`. This matches how `add_shapes` consumes the box:
+    /// a rect from `o` to `c`, a whisker from `c` to `h`
+    /// drawn only while `h > c`, a whisker from `o` to `l` drawn only while
+    /// `l < o`, and an unconditional tick at `v`. A single-element slice
+    /// collapses all five values to that sample; an empty slice returns `None`.
+    pub fn from_samples_with_fence(samples: &[f64], fence: f64) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f64::total_cmp);
+
+        let percentile = |p: f64| -> f64 {
+            let h = p / 100.0 * (sorted.len() - 1) as f64;
+            let lo = h.floor();
+            let hi = h.ceil();
+            let v_lo = sorted[lo as usize];
+            let v_hi = sorted[hi as usize];
+            v_lo + (h - lo) * (v_hi - v_lo)
+        };
+
+        let q1 = percentile(25.0);
+        let median = percentile(50.0);
+        let q3 = percentile(75.0);
+        let iqr = q3 - q1;
+        let low_fence = q1 - fence * iqr;
+        let high_fence = q3 + fence * iqr;
+
+        let whisker_low = sorted
+            .iter()
+            .copied()
+            .filter(|v| *v >= low_fence)
+            .fold(sorted[sorted.len() - 1], f64::min);
+        let whisker_high = sorted
+            .iter()
+            .copied()
+            .filter(|v| *v <= high_fence)
+            .fold(sorted[0], f64::max);
+
+        Some(Self {
+            o: q1,
+            h: whisker_high,
+            l: whisker_low,
+            c: q3,
+            v: median,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::KlineData;
+
+    #[test]
+    fn from_samples_matches_box_and_whisker_rendering() {
+        let samples = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0];
+
+        let data = KlineData::from_samples(&samples).unwrap();
+
+        // Box edges: Q1..Q3.
+        assert_eq!(data.o, 3.0);
+        assert_eq!(data.c, 7.0);
+        // Median tick, drawn unconditionally at `v`.
+        assert_eq!(data.v, 5.0);
+        // Whiskers: all samples fall within the 1.5 * IQR fences, so they
+        // land on the actual min/max and both whisker lines draw
+        // (`h > c` and `l < o`).
+        assert_eq!(data.h, 9.0);
+        assert_eq!(data.l, 1.0);
+        assert!(data.h > data.c);
+        assert!(data.l < data.o);
+    }
+
+    #[test]
+    fn from_samples_single_value_collapses() {
+        let data = KlineData::from_samples(&[42.0]).unwrap();
+        assert_eq!(data, KlineData::new(42.0, 42.0, 42.0, 42.0, 42.0));
+    }
+
+    #[test]
+    fn from_samples_empty_is_none() {
+        assert_eq!(KlineData::from_samples(&[]), None);
+    }
 }
 
 /// A box in a [`BoxPlot`] diagram.
 ///
 /// This is a low-level graphical element; it will not compute quartiles and whiskers, letting one
 /// use their preferred formula. Use [`Points`][`super::Points`] to draw the outliers.
+///
+/// Resolves its argument/value axis mapping from `orientation` at draw time;
+/// for hot redraw loops over large series where the orientation is known up
+/// front, see [`GenericKlinePlotPoint`][`super::kline_orient::GenericKlinePlotPoint`] instead.
 #[derive(Clone, Debug, PartialEq)]
 pub struct KlinePlotPoint{
     /// Name of plot element in the diagram (annotated by default formatter).
@@ -144,86 +252,52 @@ impl KlinePlotPoint{
         self
     }
 
+    /// Draw this candle's shapes, colored from `parent`'s
+    /// [`KlinePlot::rising_fill`]/[`KlinePlot::falling_fill`] palette (and
+    /// matching strokes) unless [`Self::fill`] has been set explicitly.
+    ///
+    /// Matches on `self.orientation` once, here, to pick `Vertical::make_coord`
+    /// or `Horizontal::make_coord`, then forwards straight into the free
+    /// functions shared with [`GenericKlinePlotPoint`][`super::kline_orient::GenericKlinePlotPoint`]
+    /// -- no owned copy of `self` is built to get there.
     pub(super) fn add_shapes(
         &self,
+        parent: &KlinePlot,
         transform: &PlotTransform,
         highlighted: bool,
         shapes: &mut Vec<Shape>,
     ) {
-        let (stroke, fill) = if highlighted {
-            highlighted_color(self.stroke, self.fill)
-        } else {
-            (self.stroke, self.fill)
-        };
-
-        let rect = transform.rect_from_values(
-            &self.point_at(self.argument - self.box_width / 2.0, self.spread.o),
-            &self.point_at(self.argument + self.box_width / 2.0, self.spread.c),
-        );
-        let rect = Shape::Rect(RectShape::new(
-            rect,
-            CornerRadius::ZERO,
-            fill,
-            stroke,
-            egui::StrokeKind::Inside,
-        ));
-        shapes.push(rect);
-
-        let line_between = |v1, v2| {
-            Shape::line_segment(
-                [
-                    transform.position_from_point(&v1),
-                    transform.position_from_point(&v2),
-                ],
-                stroke,
-            )
-        };
-        let v = line_between(
-            self.point_at(self.argument - self.box_width / 2.0, self.spread.v),
-            self.point_at(self.argument + self.box_width / 2.0, self.spread.v),
+        let rising_falling = (
+            (parent.rising_stroke, parent.rising_fill),
+            (parent.falling_stroke, parent.falling_fill),
         );
-        shapes.push(v);
-
-        if self.spread.h > self.spread.c {
-            let high_whisker = line_between(
-                self.point_at(self.argument, self.spread.c),
-                self.point_at(self.argument, self.spread.h),
-            );
-            shapes.push(high_whisker);
-            if self.box_width > 0.0 {
-                let high_whisker_end = line_between(
-                    self.point_at(
-                        self.argument - self.whisker_width / 2.0,
-                        self.spread.h,
-                    ),
-                    self.point_at(
-                        self.argument + self.whisker_width / 2.0,
-                        self.spread.h,
-                    ),
-                );
-                shapes.push(high_whisker_end);
-            }
-        }
-
-        if self.spread.l < self.spread.o {
-            let low_whisker = line_between(
-                self.point_at(self.argument, self.spread.o),
-                self.point_at(self.argument, self.spread.l),
-            );
-            shapes.push(low_whisker);
-            if self.box_width > 0.0 {
-                let low_whisker_end = line_between(
-                    self.point_at(
-                        self.argument - self.whisker_width / 2.0,
-                        self.spread.l,
-                    ),
-                    self.point_at(
-                        self.argument + self.whisker_width / 2.0,
-                        self.spread.l,
-                    ),
-                );
-                shapes.push(low_whisker_end);
-            }
+        match self.orientation {
+            Orientation::Vertical => add_shapes_impl(
+                self.argument,
+                &self.spread,
+                self.box_width,
+                self.whisker_width,
+                self.stroke,
+                self.fill,
+                rising_falling,
+                highlighted,
+                transform,
+                Vertical::make_coord,
+                shapes,
+            ),
+            Orientation::Horizontal => add_shapes_impl(
+                self.argument,
+                &self.spread,
+                self.box_width,
+                self.whisker_width,
+                self.stroke,
+                self.fill,
+                rising_falling,
+                highlighted,
+                transform,
+                Horizontal::make_coord,
+                shapes,
+            ),
         }
     }
 
@@ -249,25 +323,52 @@ impl RectElement for KlinePlotPoint {
     }
 
     fn bounds_min(&self) -> PlotPoint {
-        let argument = self.argument - self.box_width.max(self.whisker_width) / 2.0;
-        let value = self.spread.l;
-        self.point_at(argument, value)
+        match self.orientation {
+            Orientation::Vertical => bounds_min_impl(
+                self.argument,
+                self.box_width,
+                self.whisker_width,
+                &self.spread,
+                Vertical::make_coord,
+            ),
+            Orientation::Horizontal => bounds_min_impl(
+                self.argument,
+                self.box_width,
+                self.whisker_width,
+                &self.spread,
+                Horizontal::make_coord,
+            ),
+        }
     }
 
     fn bounds_max(&self) -> PlotPoint {
-        let argument = self.argument + self.box_width.max(self.whisker_width) / 2.0;
-        let value = self.spread.h;
-        self.point_at(argument, value)
+        match self.orientation {
+            Orientation::Vertical => bounds_max_impl(
+                self.argument,
+                self.box_width,
+                self.whisker_width,
+                &self.spread,
+                Vertical::make_coord,
+            ),
+            Orientation::Horizontal => bounds_max_impl(
+                self.argument,
+                self.box_width,
+                self.whisker_width,
+                &self.spread,
+                Horizontal::make_coord,
+            ),
+        }
     }
 
     fn values_with_ruler(&self) -> Vec<PlotPoint> {
-        let v = self.point_at(self.argument, self.spread.v);
-        let q1 = self.point_at(self.argument, self.spread.o);
-        let q3 = self.point_at(self.argument, self.spread.c);
-        let upper = self.point_at(self.argument, self.spread.h);
-        let lower = self.point_at(self.argument, self.spread.l);
-
-        vec![v, q1, q3, upper, lower]
+        match self.orientation {
+            Orientation::Vertical => {
+                values_with_ruler_impl(self.argument, &self.spread, Vertical::make_coord)
+            }
+            Orientation::Horizontal => {
+                values_with_ruler_impl(self.argument, &self.spread, Horizontal::make_coord)
+            }
+        }
     }
 
     fn orientation(&self) -> Orientation {
@@ -275,30 +376,69 @@ impl RectElement for KlinePlotPoint {
     }
 
     fn corner_value(&self) -> PlotPoint {
-        self.point_at(self.argument, self.spread.h)
+        match self.orientation {
+            Orientation::Vertical => Vertical::make_coord(self.argument, self.spread.h),
+            Orientation::Horizontal => Horizontal::make_coord(self.argument, self.spread.h),
+        }
     }
 
     fn default_values_format(&self, transform: &PlotTransform) -> String {
-        let scale = transform.dvalue_dpos();
-        let scale = match self.orientation {
-            Orientation::Horizontal => scale[0],
-            Orientation::Vertical => scale[1],
-        };
-        let y_decimals = ((-scale.abs().log10()).ceil().at_least(0.0) as usize)
-            .at_most(6)
-            .at_least(1);
-        format!(
-            "Max = {max:.decimals$}\
-             \nQuartile 3 = {q3:.decimals$}\
-             \nMedian = {med:.decimals$}\
-             \nQuartile 1 = {q1:.decimals$}\
-             \nMin = {min:.decimals$}",
-            max = self.spread.h,
-            q3 = self.spread.c,
-            med = self.spread.v,
-            q1 = self.spread.o,
-            min = self.spread.l,
-            decimals = y_decimals
-        )
+        default_values_format_impl(self.orientation, &self.spread, transform)
+    }
+}
+
+impl From<Vec<[f64; 6]>> for KlinePlot {
+    /// Build a candlestick series from `[argument, o, h, l, c, v]` rows, so
+    /// OHLCV data loaded straight from CSV/JSON maps onto a chart in one
+    /// expression.
+    fn from(data: Vec<[f64; 6]>) -> Self {
+        data.into_iter()
+            .map(|[argument, o, h, l, c, v]| {
+                KlinePlotPoint::new(argument, KlineData::new(o, h, l, c, v))
+            })
+            .collect()
+    }
+}
+
+impl FromIterator<KlinePlotPoint> for KlinePlot {
+    fn from_iter<I: IntoIterator<Item = KlinePlotPoint>>(iter: I) -> Self {
+        Self::new(String::new(), iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod point_tests {
+    use super::{KlineData, KlinePlotPoint, RectElement};
+    use super::super::kline_orient::{GenericKlinePlotPoint, Vertical};
+
+    fn make(orientation_vertical: bool) -> KlinePlotPoint {
+        let point = KlinePlotPoint::new(3.0, KlineData::new(2.0, 9.0, 1.0, 5.0, 1_000.0));
+        if orientation_vertical { point.vertical() } else { point }
+    }
+
+    fn make_generic() -> GenericKlinePlotPoint<Vertical> {
+        GenericKlinePlotPoint::new(3.0, KlineData::new(2.0, 9.0, 1.0, 5.0, 1_000.0))
+    }
+
+    #[test]
+    fn vertical_kline_plot_point_matches_generic_bounds() {
+        let dynamic = make(true);
+        let generic = make_generic();
+        assert_eq!(dynamic.bounds_min(), generic.bounds_min());
+        assert_eq!(dynamic.bounds_max(), generic.bounds_max());
+    }
+
+    #[test]
+    fn vertical_kline_plot_point_matches_generic_ruler_values() {
+        let dynamic = make(true);
+        let generic = make_generic();
+        assert_eq!(dynamic.values_with_ruler(), generic.values_with_ruler());
+    }
+
+    #[test]
+    fn vertical_kline_plot_point_matches_generic_corner_value() {
+        let dynamic = make(true);
+        let generic = make_generic();
+        assert_eq!(dynamic.corner_value(), generic.corner_value());
     }
 }
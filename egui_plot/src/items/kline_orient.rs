@@ -0,0 +1,595 @@
+use std::marker::PhantomData;
+
+use egui::emath::NumExt as _;
+use egui::epaint::{Color32, CornerRadius, RectShape, Shape, Stroke};
+
+use crate::{Cursor, PlotPoint, PlotTransform};
+
+use super::kline_elem::KlineData;
+use super::ordered_float::{finite_max, finite_min};
+use super::{Orientation, PlotConfig, RectElement, add_rulers_and_text, highlighted_color};
+
+/// Compile-time orientation marker for [`GenericKlinePlotPoint`], mirroring
+/// plotters' `BoxplotOrient`.
+///
+/// Implementors resolve the argument/value axis mapping at compile time, so
+/// [`GenericKlinePlotPoint`] passes `O::make_coord` straight through and skips
+/// the runtime [`Orientation`] match that
+/// [`KlinePlotPoint`][`super::kline_elem::KlinePlotPoint`] makes on each call.
+pub trait KlineOrient {
+    /// The runtime [`Orientation`] this marker corresponds to, for interop
+    /// with the dynamic, enum-based API.
+    const ORIENTATION: Orientation;
+
+    /// Map an `(argument, value)` pair onto plot coordinates for this axis.
+    fn make_coord(argument: f64, value: f64) -> PlotPoint;
+}
+
+/// Marker for a vertical candlestick series: argument on X, value on Y.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vertical;
+
+impl KlineOrient for Vertical {
+    const ORIENTATION: Orientation = Orientation::Vertical;
+
+    fn make_coord(argument: f64, value: f64) -> PlotPoint {
+        PlotPoint::new(argument, value)
+    }
+}
+
+/// Marker for a horizontal candlestick series: argument on Y, value on X.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Horizontal;
+
+impl KlineOrient for Horizontal {
+    const ORIENTATION: Orientation = Orientation::Horizontal;
+
+    fn make_coord(argument: f64, value: f64) -> PlotPoint {
+        PlotPoint::new(value, argument)
+    }
+}
+
+/// Colors to use for a rising candle (close >= open) and a falling one,
+/// shared by [`KlinePlot`][`super::kline_plot::KlinePlot`] and
+/// [`GenericKlinePlot`] so the coloring logic below only has to know about
+/// plain tuples, not either concrete parent type.
+type RisingFalling = ((Stroke, Color32), (Stroke, Color32));
+
+/// Resolve the stroke/fill a candle draws with: an explicit `own_fill` opts
+/// it out of the rising/falling palette, otherwise it's colored by comparing
+/// close against open. Split out of [`add_shapes_impl`] so the coloring rule
+/// is unit-testable without a [`PlotTransform`].
+fn candle_color(
+    spread: &KlineData,
+    own_stroke: Stroke,
+    own_fill: Color32,
+    (rising, falling): RisingFalling,
+    highlighted: bool,
+) -> (Stroke, Color32) {
+    let (stroke, fill) = if own_fill == Color32::TRANSPARENT {
+        if spread.c >= spread.o { rising } else { falling }
+    } else {
+        (own_stroke, own_fill)
+    };
+    if highlighted {
+        highlighted_color(stroke, fill)
+    } else {
+        (stroke, fill)
+    }
+}
+
+/// Shared box-and-whiskers drawing logic for both the dynamic
+/// [`KlinePlotPoint`][`super::kline_elem::KlinePlotPoint`] and the
+/// compile-time-oriented [`GenericKlinePlotPoint`]. Takes `point_at` as a
+/// parameter instead of a method so the generic caller can pass a bare
+/// `O::make_coord` function item (no per-shape branch) while the dynamic
+/// caller matches on its runtime `Orientation` once, at the call site, and
+/// passes `Vertical::make_coord` or `Horizontal::make_coord` the same way,
+/// rather than matching once per coordinate.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn add_shapes_impl(
+    argument: f64,
+    spread: &KlineData,
+    box_width: f64,
+    whisker_width: f64,
+    own_stroke: Stroke,
+    own_fill: Color32,
+    rising_falling: RisingFalling,
+    highlighted: bool,
+    transform: &PlotTransform,
+    point_at: impl Fn(f64, f64) -> PlotPoint,
+    shapes: &mut Vec<Shape>,
+) {
+    let (stroke, fill) = candle_color(spread, own_stroke, own_fill, rising_falling, highlighted);
+
+    let rect = transform.rect_from_values(
+        &point_at(argument - box_width / 2.0, spread.o),
+        &point_at(argument + box_width / 2.0, spread.c),
+    );
+    let rect = Shape::Rect(RectShape::new(
+        rect,
+        CornerRadius::ZERO,
+        fill,
+        stroke,
+        egui::StrokeKind::Inside,
+    ));
+    shapes.push(rect);
+
+    let line_between = |v1, v2| {
+        Shape::line_segment(
+            [
+                transform.position_from_point(&v1),
+                transform.position_from_point(&v2),
+            ],
+            stroke,
+        )
+    };
+    let v = line_between(
+        point_at(argument - box_width / 2.0, spread.v),
+        point_at(argument + box_width / 2.0, spread.v),
+    );
+    shapes.push(v);
+
+    if spread.h > spread.c {
+        let high_whisker = line_between(point_at(argument, spread.c), point_at(argument, spread.h));
+        shapes.push(high_whisker);
+        if box_width > 0.0 {
+            let high_whisker_end = line_between(
+                point_at(argument - whisker_width / 2.0, spread.h),
+                point_at(argument + whisker_width / 2.0, spread.h),
+            );
+            shapes.push(high_whisker_end);
+        }
+    }
+
+    if spread.l < spread.o {
+        let low_whisker = line_between(point_at(argument, spread.o), point_at(argument, spread.l));
+        shapes.push(low_whisker);
+        if box_width > 0.0 {
+            let low_whisker_end = line_between(
+                point_at(argument - whisker_width / 2.0, spread.l),
+                point_at(argument + whisker_width / 2.0, spread.l),
+            );
+            shapes.push(low_whisker_end);
+        }
+    }
+}
+
+/// Shared `RectElement::bounds_min` logic; see [`add_shapes_impl`] for why
+/// `point_at` is a parameter rather than a method.
+pub(super) fn bounds_min_impl(
+    argument: f64,
+    box_width: f64,
+    whisker_width: f64,
+    spread: &KlineData,
+    point_at: impl Fn(f64, f64) -> PlotPoint,
+) -> PlotPoint {
+    let argument = argument - box_width.max(whisker_width) / 2.0;
+    // Take the smallest finite price component, skipping NaN/infinite ones,
+    // so a single bad sample doesn't poison the plot's auto-range; only if
+    // none is finite does this fall back to `l`. `v` is excluded: series
+    // built from OHLCV rows store
+    // volume there, which isn't on the price axis and would blow out the
+    // range.
+    let value = finite_min([spread.l, spread.o, spread.h, spread.c]).unwrap_or(spread.l);
+    point_at(argument, value)
+}
+
+/// Shared `RectElement::bounds_max` logic; see [`add_shapes_impl`] for why
+/// `point_at` is a parameter rather than a method.
+pub(super) fn bounds_max_impl(
+    argument: f64,
+    box_width: f64,
+    whisker_width: f64,
+    spread: &KlineData,
+    point_at: impl Fn(f64, f64) -> PlotPoint,
+) -> PlotPoint {
+    let argument = argument + box_width.max(whisker_width) / 2.0;
+    // See `bounds_min_impl` for why `v` is excluded.
+    let value = finite_max([spread.l, spread.o, spread.h, spread.c]).unwrap_or(spread.h);
+    point_at(argument, value)
+}
+
+/// Shared `RectElement::values_with_ruler` logic; see [`add_shapes_impl`]
+/// for why `point_at` is a parameter rather than a method.
+pub(super) fn values_with_ruler_impl(
+    argument: f64,
+    spread: &KlineData,
+    point_at: impl Fn(f64, f64) -> PlotPoint,
+) -> Vec<PlotPoint> {
+    let v = point_at(argument, spread.v);
+    let q1 = point_at(argument, spread.o);
+    let q3 = point_at(argument, spread.c);
+    let upper = point_at(argument, spread.h);
+    let lower = point_at(argument, spread.l);
+
+    vec![v, q1, q3, upper, lower]
+}
+
+/// Shared `RectElement::default_values_format` logic.
+pub(super) fn default_values_format_impl(
+    orientation: Orientation,
+    spread: &KlineData,
+    transform: &PlotTransform,
+) -> String {
+    let scale = transform.dvalue_dpos();
+    let scale = match orientation {
+        Orientation::Horizontal => scale[0],
+        Orientation::Vertical => scale[1],
+    };
+    let y_decimals = ((-scale.abs().log10()).ceil().at_least(0.0) as usize)
+        .at_most(6)
+        .at_least(1);
+    format!(
+        "Max = {max:.decimals$}\
+         \nQuartile 3 = {q3:.decimals$}\
+         \nMedian = {med:.decimals$}\
+         \nQuartile 1 = {q1:.decimals$}\
+         \nMin = {min:.decimals$}",
+        max = spread.h,
+        q3 = spread.c,
+        med = spread.v,
+        q1 = spread.o,
+        min = spread.l,
+        decimals = y_decimals
+    )
+}
+
+/// Compile-time-oriented twin of [`KlinePlotPoint`][`super::kline_elem::KlinePlotPoint`].
+///
+/// `O` fixes the argument/value axis mapping at the type level instead of
+/// storing a runtime [`Orientation`] field, so hot redraw loops over large
+/// series avoid per-shape match dispatch. Build a series of these into a
+/// [`GenericKlinePlot<O>`] the same way [`KlinePlotPoint`][`super::kline_elem::KlinePlotPoint`]s
+/// build a [`KlinePlot`][`super::kline_plot::KlinePlot`]; reach for the
+/// dynamic, enum-based type instead when the orientation is only known at
+/// runtime.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GenericKlinePlotPoint<O: KlineOrient> {
+    /// Name of plot element in the diagram (annotated by default formatter).
+    pub name: String,
+
+    /// Position on the argument (input) axis -- X if vertical, Y if horizontal.
+    pub argument: f64,
+
+    /// Values of the box.
+    pub spread: KlineData,
+
+    /// Thickness of the box.
+    pub box_width: f64,
+
+    /// Width of the whisker at minimum/maximum.
+    pub whisker_width: f64,
+
+    /// Line width and color.
+    pub stroke: Stroke,
+
+    /// Fill color.
+    pub fill: Color32,
+
+    pub(crate) _orientation: PhantomData<O>,
+}
+
+/// A [`GenericKlinePlotPoint`] fixed to vertical (argument on X, value on Y).
+pub type KlinePlotPointV = GenericKlinePlotPoint<Vertical>;
+
+/// A [`GenericKlinePlotPoint`] fixed to horizontal (argument on Y, value on X).
+pub type KlinePlotPointH = GenericKlinePlotPoint<Horizontal>;
+
+impl<O: KlineOrient> GenericKlinePlotPoint<O> {
+    /// Create a box element with its orientation fixed at compile time by `O`.
+    pub fn new(argument: f64, spread: KlineData) -> Self {
+        Self {
+            argument,
+            name: String::default(),
+            spread,
+            box_width: 0.25,
+            whisker_width: 0.15,
+            stroke: Stroke::new(1.0, Color32::TRANSPARENT),
+            fill: Color32::TRANSPARENT,
+            _orientation: PhantomData,
+        }
+    }
+
+    /// Name of this box element.
+    #[allow(clippy::needless_pass_by_value)]
+    #[inline]
+    pub fn name(mut self, name: impl ToString) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Add a custom stroke.
+    #[inline]
+    pub fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.stroke = stroke.into();
+        self
+    }
+
+    /// Add a custom fill color.
+    #[inline]
+    pub fn fill(mut self, color: impl Into<Color32>) -> Self {
+        self.fill = color.into();
+        self
+    }
+
+    /// Set the box width.
+    #[inline]
+    pub fn box_width(mut self, width: f64) -> Self {
+        self.box_width = width;
+        self
+    }
+
+    /// Set the whisker width.
+    #[inline]
+    pub fn whisker_width(mut self, width: f64) -> Self {
+        self.whisker_width = width;
+        self
+    }
+
+    /// Draw this candle's shapes, colored from `parent`'s rising/falling
+    /// palette unless [`Self::fill`] has been set explicitly. `O::make_coord`
+    /// is passed straight through as a zero-cost function item, so this has
+    /// no runtime orientation branch.
+    pub(super) fn add_shapes(
+        &self,
+        parent: &GenericKlinePlot<O>,
+        transform: &PlotTransform,
+        highlighted: bool,
+        shapes: &mut Vec<Shape>,
+    ) {
+        add_shapes_impl(
+            self.argument,
+            &self.spread,
+            self.box_width,
+            self.whisker_width,
+            self.stroke,
+            self.fill,
+            (
+                (parent.rising_stroke, parent.rising_fill),
+                (parent.falling_stroke, parent.falling_fill),
+            ),
+            highlighted,
+            transform,
+            O::make_coord,
+            shapes,
+        );
+    }
+
+    pub(super) fn add_rulers_and_text(
+        &self,
+        parent: &GenericKlinePlot<O>,
+        plot: &PlotConfig<'_>,
+        shapes: &mut Vec<Shape>,
+        cursors: &mut Vec<Cursor>,
+    ) {
+        let text: Option<String> = parent
+            .element_formatter
+            .as_ref()
+            .map(|fmt| fmt(self, parent));
+
+        add_rulers_and_text(self, plot, text, shapes, cursors);
+    }
+}
+
+impl<O: KlineOrient> RectElement for GenericKlinePlotPoint<O> {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn bounds_min(&self) -> PlotPoint {
+        bounds_min_impl(
+            self.argument,
+            self.box_width,
+            self.whisker_width,
+            &self.spread,
+            O::make_coord,
+        )
+    }
+
+    fn bounds_max(&self) -> PlotPoint {
+        bounds_max_impl(
+            self.argument,
+            self.box_width,
+            self.whisker_width,
+            &self.spread,
+            O::make_coord,
+        )
+    }
+
+    fn values_with_ruler(&self) -> Vec<PlotPoint> {
+        values_with_ruler_impl(self.argument, &self.spread, O::make_coord)
+    }
+
+    fn orientation(&self) -> Orientation {
+        O::ORIENTATION
+    }
+
+    fn corner_value(&self) -> PlotPoint {
+        O::make_coord(self.argument, self.spread.h)
+    }
+
+    fn default_values_format(&self, transform: &PlotTransform) -> String {
+        default_values_format_impl(O::ORIENTATION, &self.spread, transform)
+    }
+}
+
+/// A candlestick series with its orientation fixed at compile time by `O`,
+/// analogous to [`KlinePlot`][`super::kline_plot::KlinePlot`] but holding
+/// [`GenericKlinePlotPoint<O>`] instead of the dynamic, enum-oriented
+/// [`KlinePlotPoint`][`super::kline_elem::KlinePlotPoint`]. This is the
+/// container that actually makes a series of `GenericKlinePlotPoint`s
+/// plottable.
+pub struct GenericKlinePlot<O: KlineOrient> {
+    pub(super) name: String,
+    pub(super) boxes: Vec<GenericKlinePlotPoint<O>>,
+
+    /// Fill used for candles whose close is greater than or equal to their
+    /// open, unless the candle has its own [`GenericKlinePlotPoint::fill`] set.
+    pub rising_fill: Color32,
+
+    /// Fill used for candles whose close is below their open, unless the
+    /// candle has its own [`GenericKlinePlotPoint::fill`] set.
+    pub falling_fill: Color32,
+
+    /// Stroke used alongside [`Self::rising_fill`].
+    pub rising_stroke: Stroke,
+
+    /// Stroke used alongside [`Self::falling_fill`].
+    pub falling_stroke: Stroke,
+
+    #[allow(clippy::type_complexity)]
+    pub(super) element_formatter: Option<Box<dyn Fn(&GenericKlinePlotPoint<O>, &Self) -> String>>,
+}
+
+/// A [`GenericKlinePlot`] fixed to vertical (argument on X, value on Y).
+pub type KlinePlotV = GenericKlinePlot<Vertical>;
+
+/// A [`GenericKlinePlot`] fixed to horizontal (argument on Y, value on X).
+pub type KlinePlotH = GenericKlinePlot<Horizontal>;
+
+impl<O: KlineOrient> GenericKlinePlot<O> {
+    /// Create a candlestick plot. `name` is used as the label in the legend.
+    pub fn new(name: impl Into<String>, boxes: Vec<GenericKlinePlotPoint<O>>) -> Self {
+        Self {
+            name: name.into(),
+            boxes,
+            rising_fill: Color32::from_rgb(8, 153, 129),
+            falling_fill: Color32::from_rgb(242, 54, 69),
+            rising_stroke: Stroke::new(1.0, Color32::from_rgb(8, 153, 129)),
+            falling_stroke: Stroke::new(1.0, Color32::from_rgb(242, 54, 69)),
+            element_formatter: None,
+        }
+    }
+
+    /// Set the fill used for rising candles (close >= open).
+    #[inline]
+    pub fn rising_fill(mut self, color: impl Into<Color32>) -> Self {
+        self.rising_fill = color.into();
+        self
+    }
+
+    /// Set the fill used for falling candles (close < open).
+    #[inline]
+    pub fn falling_fill(mut self, color: impl Into<Color32>) -> Self {
+        self.falling_fill = color.into();
+        self
+    }
+
+    /// Set the stroke used for rising candles (close >= open).
+    #[inline]
+    pub fn rising_stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.rising_stroke = stroke.into();
+        self
+    }
+
+    /// Set the stroke used for falling candles (close < open).
+    #[inline]
+    pub fn falling_stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.falling_stroke = stroke.into();
+        self
+    }
+}
+
+impl<O: KlineOrient> From<Vec<[f64; 6]>> for GenericKlinePlot<O> {
+    /// Build a candlestick series from `[argument, o, h, l, c, v]` rows, so
+    /// OHLCV data loaded straight from CSV/JSON maps onto a chart in one
+    /// expression.
+    fn from(data: Vec<[f64; 6]>) -> Self {
+        data.into_iter()
+            .map(|[argument, o, h, l, c, v]| {
+                GenericKlinePlotPoint::new(argument, KlineData::new(o, h, l, c, v))
+            })
+            .collect()
+    }
+}
+
+impl<O: KlineOrient> FromIterator<GenericKlinePlotPoint<O>> for GenericKlinePlot<O> {
+    fn from_iter<I: IntoIterator<Item = GenericKlinePlotPoint<O>>>(iter: I) -> Self {
+        Self::new(String::new(), iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KlineOrient, Vertical, bounds_max_impl, bounds_min_impl, candle_color};
+    use super::super::kline_elem::KlineData;
+
+    const RISING: (Stroke, Color32) = (
+        Stroke { width: 1.0, color: Color32::GREEN },
+        Color32::GREEN,
+    );
+    const FALLING: (Stroke, Color32) = (
+        Stroke { width: 1.0, color: Color32::RED },
+        Color32::RED,
+    );
+
+    fn no_own_fill() -> (Stroke, Color32) {
+        (Stroke::new(1.0, Color32::TRANSPARENT), Color32::TRANSPARENT)
+    }
+
+    #[test]
+    fn rising_candle_uses_rising_palette() {
+        let spread = KlineData::new(1.0, 2.0, 0.5, 1.5, 1.0);
+        let (own_stroke, own_fill) = no_own_fill();
+        let (_, fill) = candle_color(&spread, own_stroke, own_fill, (RISING, FALLING), false);
+        assert_eq!(fill, Color32::GREEN);
+    }
+
+    #[test]
+    fn falling_candle_uses_falling_palette() {
+        let spread = KlineData::new(1.5, 2.0, 0.5, 1.0, 1.0);
+        let (own_stroke, own_fill) = no_own_fill();
+        let (_, fill) = candle_color(&spread, own_stroke, own_fill, (RISING, FALLING), false);
+        assert_eq!(fill, Color32::RED);
+    }
+
+    #[test]
+    fn equal_close_and_open_counts_as_rising() {
+        let spread = KlineData::new(1.0, 2.0, 0.5, 1.0, 1.0);
+        let (own_stroke, own_fill) = no_own_fill();
+        let (_, fill) = candle_color(&spread, own_stroke, own_fill, (RISING, FALLING), false);
+        assert_eq!(fill, Color32::GREEN);
+    }
+
+    #[test]
+    fn explicit_fill_opts_out_of_the_palette() {
+        let spread = KlineData::new(1.5, 2.0, 0.5, 1.0, 1.0);
+        let own_stroke = Stroke {
+            width: 1.0,
+            color: Color32::BLUE,
+        };
+        let (stroke, fill) = candle_color(&spread, own_stroke, Color32::BLUE, (RISING, FALLING), false);
+        assert_eq!(fill, Color32::BLUE);
+        assert_eq!(stroke, own_stroke);
+    }
+
+    #[test]
+    fn bounds_skip_nan_price_components() {
+        let spread = KlineData::new(f64::NAN, 9.0, 1.0, 5.0, 1_000.0);
+        let min = bounds_min_impl(0.0, 0.5, 0.3, &spread, Vertical::make_coord);
+        let max = bounds_max_impl(0.0, 0.5, 0.3, &spread, Vertical::make_coord);
+        // `o` is NaN, so it is skipped and the low bound is the lowest
+        // finite price component (`l`) instead of NaN.
+        assert_eq!(min.y, 1.0);
+        assert_eq!(max.y, 9.0);
+    }
+
+    #[test]
+    fn bounds_exclude_volume_even_when_it_dwarfs_the_price_range() {
+        let spread = KlineData::new(2.0, 9.0, 1.0, 5.0, 1_000.0);
+        let min = bounds_min_impl(0.0, 0.5, 0.3, &spread, Vertical::make_coord);
+        let max = bounds_max_impl(0.0, 0.5, 0.3, &spread, Vertical::make_coord);
+        assert_eq!(min.y, 1.0);
+        assert_eq!(max.y, 9.0);
+    }
+
+    #[test]
+    fn bounds_fall_back_to_the_nan_component_when_nothing_is_finite() {
+        let spread = KlineData::new(f64::NAN, f64::NAN, f64::NAN, f64::NAN, 1_000.0);
+        let min = bounds_min_impl(0.0, 0.5, 0.3, &spread, Vertical::make_coord);
+        let max = bounds_max_impl(0.0, 0.5, 0.3, &spread, Vertical::make_coord);
+        assert!(min.y.is_nan());
+        assert!(max.y.is_nan());
+    }
+}
@@ -0,0 +1,70 @@
+use egui::epaint::{Color32, Stroke};
+
+use super::kline_elem::KlinePlotPoint;
+
+/// A candlestick series, analogous to [`BoxPlot`][`super::BoxPlot`]. For a
+/// plain OHLC-bar series (high/low line with open/close ticks, no filled
+/// box), see [`OhlcBarPlot`][`super::ohlc_bar::OhlcBarPlot`] instead.
+pub struct KlinePlot {
+    pub(super) name: String,
+    pub(super) boxes: Vec<KlinePlotPoint>,
+
+    /// Fill used for candles whose close is greater than or equal to their open,
+    /// unless the candle has its own [`KlinePlotPoint::fill`] set.
+    pub rising_fill: Color32,
+
+    /// Fill used for candles whose close is below their open, unless the
+    /// candle has its own [`KlinePlotPoint::fill`] set.
+    pub falling_fill: Color32,
+
+    /// Stroke used alongside [`Self::rising_fill`].
+    pub rising_stroke: Stroke,
+
+    /// Stroke used alongside [`Self::falling_fill`].
+    pub falling_stroke: Stroke,
+
+    pub(super) element_formatter: Option<Box<dyn Fn(&KlinePlotPoint, &Self) -> String>>,
+}
+
+impl KlinePlot {
+    /// Create a candlestick plot. `name` is used as the label in the legend.
+    pub fn new(name: impl Into<String>, boxes: Vec<KlinePlotPoint>) -> Self {
+        Self {
+            name: name.into(),
+            boxes,
+            rising_fill: Color32::from_rgb(8, 153, 129),
+            falling_fill: Color32::from_rgb(242, 54, 69),
+            rising_stroke: Stroke::new(1.0, Color32::from_rgb(8, 153, 129)),
+            falling_stroke: Stroke::new(1.0, Color32::from_rgb(242, 54, 69)),
+            element_formatter: None,
+        }
+    }
+
+    /// Set the fill used for rising candles (close >= open).
+    #[inline]
+    pub fn rising_fill(mut self, color: impl Into<Color32>) -> Self {
+        self.rising_fill = color.into();
+        self
+    }
+
+    /// Set the fill used for falling candles (close < open).
+    #[inline]
+    pub fn falling_fill(mut self, color: impl Into<Color32>) -> Self {
+        self.falling_fill = color.into();
+        self
+    }
+
+    /// Set the stroke used for rising candles (close >= open).
+    #[inline]
+    pub fn rising_stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.rising_stroke = stroke.into();
+        self
+    }
+
+    /// Set the stroke used for falling candles (close < open).
+    #[inline]
+    pub fn falling_stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.falling_stroke = stroke.into();
+        self
+    }
+}
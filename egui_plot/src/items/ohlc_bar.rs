@@ -0,0 +1,279 @@
+use egui::emath::NumExt as _;
+use egui::epaint::{Color32, Shape, Stroke};
+
+use crate::{Cursor, PlotPoint, PlotTransform};
+
+use super::{Orientation, PlotConfig, RectElement, add_rulers_and_text};
+use super::kline_elem::KlineData;
+use super::ordered_float::{finite_max, finite_min};
+
+/// An OHLC bar: a vertical high/low line with left/right open/close ticks,
+/// as an alternative to the filled box drawn by
+/// [`KlinePlotPoint`][`super::kline_elem::KlinePlotPoint`].
+///
+/// Reuses [`KlineData`] and implements [`RectElement`] the same way
+/// `KlinePlotPoint` does, so both styles share tooltips and hover behavior
+/// and a series can switch candlestick <-> OHLC-bar rendering without
+/// restructuring its data.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OhlcBar {
+    /// Name of plot element in the diagram (annotated by default formatter).
+    pub name: String,
+
+    /// Which direction the bar faces in the diagram.
+    pub orientation: Orientation,
+
+    /// Position on the argument (input) axis -- X if vertical, Y if horizontal.
+    pub argument: f64,
+
+    /// Values of the bar.
+    pub spread: KlineData,
+
+    /// Width spanned by the open and close ticks together, analogous to
+    /// `KlinePlotPoint::whisker_width`; each tick is half of this long.
+    pub tick_width: f64,
+
+    /// Line width and color.
+    pub stroke: Stroke,
+}
+
+impl OhlcBar {
+    /// Create an OHLC bar element. Its `orientation` is set by its parent plot.
+    pub fn new(argument: f64, spread: KlineData) -> Self {
+        Self {
+            argument,
+            orientation: Orientation::default(),
+            name: String::default(),
+            spread,
+            tick_width: 0.15,
+            stroke: Stroke::new(1.0, Color32::TRANSPARENT),
+        }
+    }
+
+    /// Name of this bar element.
+    #[allow(clippy::needless_pass_by_value)]
+    #[inline]
+    pub fn name(mut self, name: impl ToString) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// Add a custom stroke.
+    #[inline]
+    pub fn stroke(mut self, stroke: impl Into<Stroke>) -> Self {
+        self.stroke = stroke.into();
+        self
+    }
+
+    /// Set the tick width.
+    #[inline]
+    pub fn tick_width(mut self, width: f64) -> Self {
+        self.tick_width = width;
+        self
+    }
+
+    /// Set orientation of the element as vertical. Argument axis is X.
+    #[inline]
+    pub fn vertical(mut self) -> Self {
+        self.orientation = Orientation::Vertical;
+        self
+    }
+
+    /// Set orientation of the element as horizontal. Argument axis is Y.
+    #[inline]
+    pub fn horizontal(mut self) -> Self {
+        self.orientation = Orientation::Horizontal;
+        self
+    }
+
+    pub(super) fn add_shapes(
+        &self,
+        transform: &PlotTransform,
+        highlighted: bool,
+        shapes: &mut Vec<Shape>,
+    ) {
+        let stroke = if highlighted {
+            super::highlighted_color(self.stroke, Color32::TRANSPARENT).0
+        } else {
+            self.stroke
+        };
+
+        let line_between = |v1, v2| {
+            Shape::line_segment(
+                [
+                    transform.position_from_point(&v1),
+                    transform.position_from_point(&v2),
+                ],
+                stroke,
+            )
+        };
+
+        let high_low = line_between(
+            self.point_at(self.argument, self.spread.l),
+            self.point_at(self.argument, self.spread.h),
+        );
+        shapes.push(high_low);
+
+        let open_tick = line_between(
+            self.point_at(self.argument - self.tick_width / 2.0, self.spread.o),
+            self.point_at(self.argument, self.spread.o),
+        );
+        shapes.push(open_tick);
+
+        let close_tick = line_between(
+            self.point_at(self.argument, self.spread.c),
+            self.point_at(self.argument + self.tick_width / 2.0, self.spread.c),
+        );
+        shapes.push(close_tick);
+    }
+
+    pub(super) fn add_rulers_and_text(
+        &self,
+        parent: &OhlcBarPlot,
+        plot: &PlotConfig<'_>,
+        shapes: &mut Vec<Shape>,
+        cursors: &mut Vec<Cursor>,
+    ) {
+        let text: Option<String> = parent
+            .element_formatter
+            .as_ref()
+            .map(|fmt| fmt(self, parent));
+
+        add_rulers_and_text(self, plot, text, shapes, cursors);
+    }
+}
+
+impl RectElement for OhlcBar {
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    fn bounds_min(&self) -> PlotPoint {
+        let argument = self.argument - self.tick_width / 2.0;
+        // `v` (volume) is excluded: it isn't on the price axis and would
+        // blow out the auto-range.
+        let value = finite_min([self.spread.l, self.spread.o, self.spread.h, self.spread.c])
+            .unwrap_or(self.spread.l);
+        self.point_at(argument, value)
+    }
+
+    fn bounds_max(&self) -> PlotPoint {
+        let argument = self.argument + self.tick_width / 2.0;
+        // See `bounds_min` for why `v` is excluded.
+        let value = finite_max([self.spread.l, self.spread.o, self.spread.h, self.spread.c])
+            .unwrap_or(self.spread.h);
+        self.point_at(argument, value)
+    }
+
+    fn values_with_ruler(&self) -> Vec<PlotPoint> {
+        let v = self.point_at(self.argument, self.spread.v);
+        let q1 = self.point_at(self.argument, self.spread.o);
+        let q3 = self.point_at(self.argument, self.spread.c);
+        let upper = self.point_at(self.argument, self.spread.h);
+        let lower = self.point_at(self.argument, self.spread.l);
+
+        vec![v, q1, q3, upper, lower]
+    }
+
+    fn orientation(&self) -> Orientation {
+        self.orientation
+    }
+
+    fn corner_value(&self) -> PlotPoint {
+        self.point_at(self.argument, self.spread.h)
+    }
+
+    fn default_values_format(&self, transform: &PlotTransform) -> String {
+        let scale = transform.dvalue_dpos();
+        let scale = match self.orientation {
+            Orientation::Horizontal => scale[0],
+            Orientation::Vertical => scale[1],
+        };
+        let y_decimals = ((-scale.abs().log10()).ceil().at_least(0.0) as usize)
+            .at_most(6)
+            .at_least(1);
+        format!(
+            "High = {high:.decimals$}\
+             \nOpen = {open:.decimals$}\
+             \nClose = {close:.decimals$}\
+             \nLow = {low:.decimals$}\
+             \nVolume = {volume:.decimals$}",
+            high = self.spread.h,
+            open = self.spread.o,
+            close = self.spread.c,
+            low = self.spread.l,
+            volume = self.spread.v,
+            decimals = y_decimals
+        )
+    }
+}
+
+/// An OHLC-bar series, analogous to [`KlinePlot`][`super::kline_plot::KlinePlot`]
+/// but holding [`OhlcBar`] instead of [`KlinePlotPoint`][`super::kline_elem::KlinePlotPoint`].
+///
+/// `OhlcBar` has no rising/falling fill to configure -- it draws a single
+/// stroke per bar -- so unlike `KlinePlot` this has no `rising_fill`/
+/// `falling_fill`/`rising_stroke`/`falling_stroke` fields.
+pub struct OhlcBarPlot {
+    pub(super) name: String,
+    pub(super) boxes: Vec<OhlcBar>,
+
+    pub(super) element_formatter: Option<Box<dyn Fn(&OhlcBar, &Self) -> String>>,
+}
+
+impl OhlcBarPlot {
+    /// Create an OHLC-bar plot. `name` is used as the label in the legend.
+    pub fn new(name: impl Into<String>, boxes: Vec<OhlcBar>) -> Self {
+        Self {
+            name: name.into(),
+            boxes,
+            element_formatter: None,
+        }
+    }
+}
+
+impl From<Vec<[f64; 6]>> for OhlcBarPlot {
+    /// Build an OHLC-bar series from `[argument, o, h, l, c, v]` rows, so
+    /// OHLCV data loaded straight from CSV/JSON maps onto a chart in one
+    /// expression.
+    fn from(data: Vec<[f64; 6]>) -> Self {
+        data.into_iter()
+            .map(|[argument, o, h, l, c, v]| OhlcBar::new(argument, KlineData::new(o, h, l, c, v)))
+            .collect()
+    }
+}
+
+impl FromIterator<OhlcBar> for OhlcBarPlot {
+    fn from_iter<I: IntoIterator<Item = OhlcBar>>(iter: I) -> Self {
+        Self::new(String::new(), iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KlineData, OhlcBar, RectElement};
+
+    #[test]
+    fn values_with_ruler_maps_ohlcv_fields_not_quartiles() {
+        let bar = OhlcBar::new(0.0, KlineData::new(2.0, 9.0, 1.0, 5.0, 1_000.0)).vertical();
+        let values = bar.values_with_ruler();
+        let ys: Vec<f64> = values.iter().map(|p| p.y).collect();
+        // [volume, open, close, high, low]: each ruler sits on the OHLCV
+        // field of the same position in `values_with_ruler`.
+        assert_eq!(ys, vec![1_000.0, 2.0, 5.0, 9.0, 1.0]);
+    }
+
+    #[test]
+    fn bounds_exclude_volume() {
+        let bar = OhlcBar::new(0.0, KlineData::new(2.0, 9.0, 1.0, 5.0, 1_000.0)).vertical();
+        assert_eq!(bar.bounds_min().y, 1.0);
+        assert_eq!(bar.bounds_max().y, 9.0);
+    }
+
+    #[test]
+    fn bounds_skip_nan_price_components() {
+        let bar = OhlcBar::new(0.0, KlineData::new(f64::NAN, 9.0, 1.0, 5.0, 1_000.0)).vertical();
+        assert_eq!(bar.bounds_min().y, 1.0);
+        assert_eq!(bar.bounds_max().y, 9.0);
+    }
+}
@@ -0,0 +1,58 @@
+use std::cmp::Ordering;
+
+/// A total ordering over `f64`, for NaN-robust min/max reduction.
+///
+/// `NaN` sorts as the largest value and compares equal to itself, so a fold
+/// over a span containing `NaN` doesn't poison the result the way
+/// `f64::partial_cmp`-based comparisons would.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct OrderedFloat(pub f64);
+
+impl PartialEq for OrderedFloat {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for OrderedFloat {}
+
+impl PartialOrd for OrderedFloat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedFloat {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.0.is_nan(), other.0.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self.0.partial_cmp(&other.0).unwrap_or(Ordering::Equal),
+        }
+    }
+}
+
+/// The smallest finite value in `values`, skipping `NaN`/infinite entries.
+///
+/// Returns `None` if none of the values are finite.
+pub(crate) fn finite_min(values: impl IntoIterator<Item = f64>) -> Option<f64> {
+    values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .map(OrderedFloat)
+        .min()
+        .map(|v| v.0)
+}
+
+/// The largest finite value in `values`, skipping `NaN`/infinite entries.
+///
+/// Returns `None` if none of the values are finite.
+pub(crate) fn finite_max(values: impl IntoIterator<Item = f64>) -> Option<f64> {
+    values
+        .into_iter()
+        .filter(|v| v.is_finite())
+        .map(OrderedFloat)
+        .max()
+        .map(|v| v.0)
+}